@@ -6,18 +6,47 @@ const AUTHORITATIVE_SHIFT: usize = 10;
 const TRUNCATED_SHIFT: usize = 9;
 const RECURSION_DESIRED_SHIFT: usize = 8;
 const RECURSION_AVAILABLE_SHIFT: usize = 7;
-/// Reserved for future use - zero out the bits in this field
-const Z_MASK: u16 = 0b1111_1111_1000_1111; 
+/// RFC 4035 AD bit - set when the resolver has verified all the data in the message
+const AUTHENTICATED_DATA_SHIFT: usize = 5;
+/// RFC 4035 CD bit - set to request that the server not verify DNSSEC signatures
+const CHECKING_DISABLED_SHIFT: usize = 4;
+/// Reserved for future use - zero out the bit in this field
+const Z_MASK: u16 = 0b1111_1111_1011_1111;
+
+const HEADER_LEN: usize = 12;
+
+/// Errors produced while decoding a wire-format message header.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Fewer than the 12 bytes a DNS header requires were available.
+    HeaderTooShort,
+    /// The reserved Z bit was set to 1.
+    ReservedBitsAreNonZero,
+    /// The 4 bit opcode field did not match a known `Opcode`.
+    UnknownOpcode(u16),
+    /// The 4 bit RCODE field did not match a known `ResponseCode`.
+    UnknownResponseCode(u16),
+}
 
 #[repr(u16)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 enum MessageType {
     Query = 0,
     Response = 1,
 }
 
+impl MessageType {
+    fn from_bits(bits: u16) -> MessageType {
+        if (bits >> MESSAGE_TYPE_SHIFT) & 1 == 1 {
+            MessageType::Response
+        } else {
+            MessageType::Query
+        }
+    }
+}
+
 #[repr(u16)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 enum Opcode {
     /// Standard query
     Query = 0,
@@ -25,10 +54,29 @@ enum Opcode {
     IQuery = 1,
     /// Server status request
     Status = 2,
+    /// Zone change notification (RFC 1996)
+    Notify = 4,
+    /// Dynamic update (RFC 2136)
+    Update = 5,
+}
+
+impl TryFrom<u16> for Opcode {
+    type Error = ParseError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Opcode::Query),
+            1 => Ok(Opcode::IQuery),
+            2 => Ok(Opcode::Status),
+            4 => Ok(Opcode::Notify),
+            5 => Ok(Opcode::Update),
+            other => Err(ParseError::UnknownOpcode(other)),
+        }
+    }
 }
 
 #[repr(u16)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 enum ResponseCode {
     NoError = 0,
     FormErr = 1,
@@ -36,14 +84,122 @@ enum ResponseCode {
     NXDomain = 3,
     NotImp = 4,
     Refused = 5,
+    /// Name exists when it should not (RFC 2136)
+    YXDomain = 6,
+    /// RR set exists when it should not (RFC 2136)
+    YXRRSet = 7,
+    /// RR set that should exist does not (RFC 2136)
+    NXRRSet = 8,
+    /// Server not authoritative for the zone, or not authorized (RFC 2136/2845)
+    NotAuth = 9,
+    /// Name not contained in the zone (RFC 2136)
+    NotZone = 10,
 }
 
+impl TryFrom<u16> for ResponseCode {
+    type Error = ParseError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ResponseCode::NoError),
+            1 => Ok(ResponseCode::FormErr),
+            2 => Ok(ResponseCode::ServFail),
+            3 => Ok(ResponseCode::NXDomain),
+            4 => Ok(ResponseCode::NotImp),
+            5 => Ok(ResponseCode::Refused),
+            6 => Ok(ResponseCode::YXDomain),
+            7 => Ok(ResponseCode::YXRRSet),
+            8 => Ok(ResponseCode::NXRRSet),
+            9 => Ok(ResponseCode::NotAuth),
+            10 => Ok(ResponseCode::NotZone),
+            other => Err(ParseError::UnknownResponseCode(other)),
+        }
+    }
+}
+
+#[derive(Debug)]
 struct Header { /// 16 bit identifier that is echoed back by DNS server.
     /// Used for matching outstanding requests with responses.
     id: u16,
-    flags: Flags
+    flags: Flags,
+    /// Number of entries in the question section.
+    questions: u16,
+    /// Number of resource records in the answer section.
+    answers: u16,
+    /// Number of name server resource records in the authority records section.
+    name_servers: u16,
+    /// Number of resource records in the additional records section.
+    additional: u16,
 }
 
+impl Header {
+    /// Decodes the 12-byte DNS message header, including the QDCOUNT, ANCOUNT,
+    /// NSCOUNT, and ARCOUNT fields that follow `id` and `flags` on the wire.
+    pub fn parse(buf: &[u8]) -> Result<Header, ParseError> {
+        if buf.len() < HEADER_LEN {
+            return Err(ParseError::HeaderTooShort);
+        }
+
+        let id = u16::from_be_bytes([buf[0], buf[1]]);
+        let flags = Flags::from_bits(u16::from_be_bytes([buf[2], buf[3]]))?;
+        let questions = u16::from_be_bytes([buf[4], buf[5]]);
+        let answers = u16::from_be_bytes([buf[6], buf[7]]);
+        let name_servers = u16::from_be_bytes([buf[8], buf[9]]);
+        let additional = u16::from_be_bytes([buf[10], buf[11]]);
+
+        Ok(Header {
+            id,
+            flags,
+            questions,
+            answers,
+            name_servers,
+            additional,
+        })
+    }
+
+    /// Serializes this header to its 12-byte wire format.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut buf = [0u8; HEADER_LEN];
+        buf[0..2].copy_from_slice(&self.id.to_be_bytes());
+        buf[2..4].copy_from_slice(&self.flags.get_bits().to_be_bytes());
+        buf[4..6].copy_from_slice(&self.questions.to_be_bytes());
+        buf[6..8].copy_from_slice(&self.answers.to_be_bytes());
+        buf[8..10].copy_from_slice(&self.name_servers.to_be_bytes());
+        buf[10..12].copy_from_slice(&self.additional.to_be_bytes());
+        buf
+    }
+
+    /// Appends this header's wire-format bytes to `buf`.
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_bytes());
+    }
+
+    /// Builds the header for a response to `request`, per RFC 6895 §2: the id,
+    /// opcode, RD, and CD bits must be echoed back from the query, while AA/TC/RA/AD
+    /// and the record counts are left for the responder to fill in.
+    pub fn reply_to(request: &Header) -> Header {
+        Header {
+            id: request.id,
+            flags: Flags {
+                message_type: MessageType::Response,
+                opcode: request.flags.opcode,
+                authoritative: false,
+                truncated: false,
+                recursion_desired: request.flags.recursion_desired,
+                recursion_available: false,
+                authenticated_data: false,
+                checking_disabled: request.flags.checking_disabled,
+                response_code: ResponseCode::NoError,
+            },
+            questions: 0,
+            answers: 0,
+            name_servers: 0,
+            additional: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
 struct Flags {
     /// 1 bit field that specifies whether this message 
     /// is a query or a response
@@ -63,7 +219,13 @@ struct Flags {
     /// 1 bit field. This bit is set or cleared in a response and denotes whether recursive queries
     /// are supported.
     recursion_available: bool,
-    response_code: ResponseCode, 
+    /// 1 bit field (RFC 4035). Set by the server when it has verified that all the data
+    /// in the response was authenticated according to its DNSSEC policies.
+    authenticated_data: bool,
+    /// 1 bit field (RFC 4035). Set in a query to direct the server to disable DNSSEC
+    /// signature validation.
+    checking_disabled: bool,
+    response_code: ResponseCode,
 }
 
 impl Flags {
@@ -83,9 +245,34 @@ impl Flags {
         if self.recursion_available {
             bits |= 1 << RECURSION_AVAILABLE_SHIFT;
         }
+        if self.authenticated_data {
+            bits |= 1 << AUTHENTICATED_DATA_SHIFT;
+        }
+        if self.checking_disabled {
+            bits |= 1 << CHECKING_DISABLED_SHIFT;
+        }
         bits &= Z_MASK;
         bits |= self.response_code as u16;
-        bits 
+        bits
+    }
+
+    /// Decodes a 16 bit flags word, rejecting messages that set the reserved Z bit.
+    pub fn from_bits(bits: u16) -> Result<Flags, ParseError> {
+        if bits & 0b0000_0000_0100_0000 != 0 {
+            return Err(ParseError::ReservedBitsAreNonZero);
+        }
+
+        Ok(Flags {
+            message_type: MessageType::from_bits(bits),
+            opcode: Opcode::try_from((bits >> OPCODE_SHIFT) & 0b1111)?,
+            authoritative: (bits >> AUTHORITATIVE_SHIFT) & 1 == 1,
+            truncated: (bits >> TRUNCATED_SHIFT) & 1 == 1,
+            recursion_desired: (bits >> RECURSION_DESIRED_SHIFT) & 1 == 1,
+            recursion_available: (bits >> RECURSION_AVAILABLE_SHIFT) & 1 == 1,
+            authenticated_data: (bits >> AUTHENTICATED_DATA_SHIFT) & 1 == 1,
+            checking_disabled: (bits >> CHECKING_DISABLED_SHIFT) & 1 == 1,
+            response_code: ResponseCode::try_from(bits & 0b1111)?,
+        })
     }
 }
 
@@ -98,11 +285,13 @@ fn get_bits() {
         truncated: false,
         recursion_desired: false,
         recursion_available: false,
+        authenticated_data: false,
+        checking_disabled: false,
         response_code: ResponseCode::NoError,
     };
     assert_eq!(flags.get_bits(), 0b0000_0000_0000_0000);
 
-    flags.opcode = Opcode::IQuery; 
+    flags.opcode = Opcode::IQuery;
     assert_eq!(flags.get_bits(), 0b0000_1000_0000_0000);
 
     flags.response_code = ResponseCode::Refused;
@@ -120,6 +309,157 @@ fn get_bits() {
     flags.recursion_available = true;
     assert_eq!(flags.get_bits(), 0b0000_1111_1000_0101);
 
+    flags.authenticated_data = true;
+    assert_eq!(flags.get_bits(), 0b0000_1111_1010_0101);
+
+    flags.checking_disabled = true;
+    assert_eq!(flags.get_bits(), 0b0000_1111_1011_0101);
+
     flags.message_type = MessageType::Response;
-    assert_eq!(flags.get_bits(), 0b1000_1111_1000_0101);
+    assert_eq!(flags.get_bits(), 0b1000_1111_1011_0101);
+}
+
+#[test]
+fn from_bits_round_trips_get_bits() {
+    let flags = Flags {
+        message_type: MessageType::Response,
+        opcode: Opcode::Status,
+        authoritative: true,
+        truncated: false,
+        recursion_desired: true,
+        recursion_available: true,
+        authenticated_data: true,
+        checking_disabled: false,
+        response_code: ResponseCode::ServFail,
+    };
+
+    let decoded = Flags::from_bits(flags.get_bits()).unwrap();
+    assert_eq!(decoded.get_bits(), flags.get_bits());
+}
+
+#[test]
+fn from_bits_rejects_reserved_bit() {
+    let bits = 0b0000_0000_0100_0000;
+    assert_eq!(
+        Flags::from_bits(bits).unwrap_err(),
+        ParseError::ReservedBitsAreNonZero
+    );
+}
+
+#[test]
+fn from_bits_rejects_unknown_opcode() {
+    let bits = 0b0111_1000_0000_0000;
+    assert_eq!(
+        Flags::from_bits(bits).unwrap_err(),
+        ParseError::UnknownOpcode(15)
+    );
+}
+
+#[test]
+fn from_bits_rejects_unknown_response_code() {
+    let bits = 0b0000_0000_0000_1111;
+    assert_eq!(
+        Flags::from_bits(bits).unwrap_err(),
+        ParseError::UnknownResponseCode(15)
+    );
+}
+
+#[test]
+fn header_parse_too_short() {
+    let buf = [0u8; 11];
+    assert_eq!(Header::parse(&buf).unwrap_err(), ParseError::HeaderTooShort);
+}
+
+#[test]
+fn header_parse_reads_id_and_flags() {
+    let buf = [
+        0x12, 0x34, 0b1000_0001, 0b0000_0101, 0, 1, 0, 2, 0, 3, 0, 4,
+    ];
+    let header = Header::parse(&buf).unwrap();
+    assert_eq!(header.id, 0x1234);
+    assert_eq!(header.flags.get_bits(), 0b1000_0001_0000_0101);
+    assert_eq!(header.questions, 1);
+    assert_eq!(header.answers, 2);
+    assert_eq!(header.name_servers, 3);
+    assert_eq!(header.additional, 4);
+}
+
+#[test]
+fn header_to_bytes_round_trips_through_parse() {
+    let buf = [
+        0x12, 0x34, 0b1000_0001, 0b0000_0101, 0, 1, 0, 2, 0, 3, 0, 4,
+    ];
+    let header = Header::parse(&buf).unwrap();
+    assert_eq!(header.to_bytes(), buf);
+
+    let mut written = Vec::new();
+    header.write(&mut written);
+    assert_eq!(written, buf);
+}
+
+#[test]
+fn reply_to_echoes_request_fields() {
+    let request = Header {
+        id: 0xABCD,
+        flags: Flags {
+            message_type: MessageType::Query,
+            opcode: Opcode::Status,
+            authoritative: false,
+            truncated: false,
+            recursion_desired: true,
+            recursion_available: false,
+            authenticated_data: false,
+            checking_disabled: true,
+            response_code: ResponseCode::NoError,
+        },
+        questions: 1,
+        answers: 0,
+        name_servers: 0,
+        additional: 0,
+    };
+
+    let response = Header::reply_to(&request);
+    assert_eq!(response.id, request.id);
+    assert!(matches!(response.flags.message_type, MessageType::Response));
+    assert!(matches!(response.flags.opcode, Opcode::Status));
+    assert_eq!(
+        response.flags.recursion_desired,
+        request.flags.recursion_desired
+    );
+    assert_eq!(
+        response.flags.checking_disabled,
+        request.flags.checking_disabled
+    );
+    assert!(!response.flags.authoritative);
+    assert_eq!(response.questions, 0);
+}
+
+#[test]
+fn opcode_decodes_notify_and_update() {
+    assert!(matches!(Opcode::try_from(4).unwrap(), Opcode::Notify));
+    assert!(matches!(Opcode::try_from(5).unwrap(), Opcode::Update));
+}
+
+#[test]
+fn response_code_decodes_update_prerequisite_codes() {
+    assert!(matches!(
+        ResponseCode::try_from(6).unwrap(),
+        ResponseCode::YXDomain
+    ));
+    assert!(matches!(
+        ResponseCode::try_from(7).unwrap(),
+        ResponseCode::YXRRSet
+    ));
+    assert!(matches!(
+        ResponseCode::try_from(8).unwrap(),
+        ResponseCode::NXRRSet
+    ));
+    assert!(matches!(
+        ResponseCode::try_from(9).unwrap(),
+        ResponseCode::NotAuth
+    ));
+    assert!(matches!(
+        ResponseCode::try_from(10).unwrap(),
+        ResponseCode::NotZone
+    ));
 }